@@ -0,0 +1,46 @@
+use anyhow::{anyhow, Result};
+use gw_common::state::State;
+use gw_config::Config;
+use gw_types::prelude::*;
+
+// Diffs each block's declared pre-state root against the reconstructed root of the block before it.
+pub async fn verify(config: Config, from_block: Option<u64>, to_block: Option<u64>) -> Result<()> {
+    let (state, blocks) = crate::runner::load_state_and_blocks(&config).await?;
+    let tip_number = blocks.tip_number();
+    let from_block = from_block.unwrap_or(1);
+    let to_block = to_block.unwrap_or(tip_number);
+
+    if from_block == 0 {
+        return Err(anyhow!(
+            "--from-block must be >= 1: block 0 is genesis and has no pre-state to diff against"
+        ));
+    }
+
+    for number in from_block..=to_block {
+        let block = blocks
+            .get_block(number)
+            .ok_or_else(|| anyhow!("block {} not found in db", number))?;
+        let declared_prev_root: [u8; 32] = block.raw().prev_account().merkle_root().unpack();
+
+        if number == 1 {
+            // Block 0 is genesis: push_block was never called for it, so
+            // state_at_block(0) has no state-root index entry to reconstruct
+            // from. Block 1's declared pre-state root is the trusted genesis
+            // root; verification of transitions starts at block 2.
+            continue;
+        }
+
+        let reconstructed_root: [u8; 32] = state.state_at_block(number - 1)?.calculate_root()?;
+
+        if reconstructed_root != declared_prev_root {
+            return Err(anyhow!(
+                "state-transition regression at block {}: reconstructed pre-state root {:?} != declared {:?}",
+                number,
+                reconstructed_root,
+                declared_prev_root,
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -4,7 +4,7 @@ static GLOBAL_ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
 use anyhow::{Context, Result};
 use clap::{App, Arg, SubCommand};
-use gw_block_producer::{db_block_validator, runner, trace};
+use gw_block_producer::{db_block_validator, runner, snapshot, trace};
 use gw_config::Config;
 use gw_version::Version;
 use std::{env, fs, path::Path};
@@ -12,11 +12,14 @@ use std::{env, fs, path::Path};
 const COMMAND_RUN: &str = "run";
 const COMMAND_EXAMPLE_CONFIG: &str = "generate-example-config";
 const COMMAND_VERIFY_DB_BLOCK: &str = "verify-db-block";
+const COMMAND_SNAPSHOT: &str = "snapshot";
 const ARG_OUTPUT_PATH: &str = "output-path";
 const ARG_CONFIG: &str = "config";
 const ARG_SKIP_CONFIG_CHECK: &str = "skip-config-check";
 const ARG_FROM_BLOCK: &str = "from-block";
 const ARG_TO_BLOCK: &str = "to-block";
+const ARG_EXPORT: &str = "export";
+const ARG_IMPORT: &str = "import";
 
 fn read_config<P: AsRef<Path>>(path: P) -> Result<Config> {
     let content = fs::read(&path)
@@ -94,6 +97,33 @@ async fn run_cli() -> Result<()> {
                         .help("To block number"),
                 )
                 .display_order(2),
+        )
+        .subcommand(
+            SubCommand::with_name(COMMAND_SNAPSHOT)
+                .about("Export or import a versioned state snapshot")
+                .arg(
+                    Arg::with_name(ARG_CONFIG)
+                        .short("c")
+                        .takes_value(true)
+                        .required(true)
+                        .default_value("./config.toml")
+                        .help("The config file path"),
+                )
+                .arg(
+                    Arg::with_name(ARG_EXPORT)
+                        .long(ARG_EXPORT)
+                        .takes_value(true)
+                        .conflicts_with(ARG_IMPORT)
+                        .help("Export the current state to the given snapshot file"),
+                )
+                .arg(
+                    Arg::with_name(ARG_IMPORT)
+                        .long(ARG_IMPORT)
+                        .takes_value(true)
+                        .conflicts_with(ARG_EXPORT)
+                        .help("Import state from the given snapshot file"),
+                )
+                .display_order(3),
         );
 
     // handle subcommands
@@ -118,6 +148,16 @@ async fn run_cli() -> Result<()> {
             let to_block: Option<u64> = m.value_of(ARG_TO_BLOCK).map(str::parse).transpose()?;
             db_block_validator::verify(config, from_block, to_block).await?;
         }
+        (COMMAND_SNAPSHOT, Some(m)) => {
+            let config_path = m.value_of(ARG_CONFIG).unwrap();
+            let config = read_config(&config_path)?;
+            let _guard = trace::init(None)?;
+            match (m.value_of(ARG_EXPORT), m.value_of(ARG_IMPORT)) {
+                (Some(path), None) => snapshot::export(config, path).await?,
+                (None, Some(path)) => snapshot::import(config, path).await?,
+                _ => return Err(anyhow::anyhow!("specify exactly one of --export or --import")),
+            }
+        }
         _ => {
             // default command: start a Godwoken node
             let config_path = "./config.toml";
@@ -0,0 +1,32 @@
+use anyhow::{anyhow, Result};
+use gw_chain::state_impl::StateImpl;
+use gw_config::Config;
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+};
+
+/// Export the current state (account tree + block tree) to `output_path` so
+/// another node can bootstrap from it instead of replaying every block.
+pub async fn export<P: AsRef<Path>>(config: Config, output_path: P) -> Result<()> {
+    let state = crate::runner::load_state(&config).await?;
+    let file = File::create(output_path)?;
+    let mut writer = BufWriter::new(file);
+    state.export_snapshot(&mut writer)?;
+    writer.flush()?;
+    writer
+        .into_inner()
+        .map_err(|err| anyhow!("flush snapshot file: {}", err))?
+        .sync_all()?;
+    Ok(())
+}
+
+/// Import a snapshot produced by `export` and persist it as the node's
+/// starting state. The manifest roots are re-verified before anything is
+/// written, so a corrupt snapshot fails before it can replace local state.
+pub async fn import<P: AsRef<Path>>(config: Config, input_path: P) -> Result<()> {
+    let mut reader = BufReader::new(File::open(input_path)?);
+    let state = StateImpl::import_snapshot(&mut reader)?;
+    crate::runner::persist_state(&config, state).await
+}
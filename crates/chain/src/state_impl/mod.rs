@@ -0,0 +1,16 @@
+// `proof_verifier` has no Store/tokio dependency; it's the part of this
+// module a future no-std-friendly `light-client` build would keep. Gating
+// the others on that feature needs a `light-client = []` feature declared
+// in gw-chain's Cargo.toml first (none exists in this tree yet), so for now
+// everything stays unconditional rather than gating on a feature that
+// doesn't exist.
+mod overlay;
+mod proof_verifier;
+mod snapshot;
+mod state_impl;
+mod wrap_store;
+
+pub use overlay::OverlayState;
+pub use proof_verifier::{verify_account_proof, verify_block_proof};
+pub use state_impl::StateImpl;
+pub use wrap_store::WrapStore;
@@ -0,0 +1,92 @@
+// Only touches gw_common's compiled-proof and blake2b primitives (no
+// Store, no tokio). It is compiled together with the rest of `state_impl`;
+// no cargo feature builds it apart from the crate's node dependencies.
+
+use anyhow::Result;
+use gw_common::{
+    blake2b::{new_blake2b, Blake2bHasher},
+    merkle_utils::serialize_block_key,
+    smt::CompiledMerkleProof,
+};
+
+// Binds a block hash to the account root it produced, so both are checked
+// as one block-tree leaf (see `StateImpl::push_block`).
+pub(crate) fn block_commitment(block_hash: &[u8; 32], state_root: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = new_blake2b();
+    hasher.update(block_hash);
+    hasher.update(state_root);
+    let mut commitment = [0u8; 32];
+    hasher.finalize(&mut commitment);
+    commitment
+}
+
+/// Verify that `leaves` are included in the account tree rooted at `root`.
+pub fn verify_account_proof(
+    root: [u8; 32],
+    leaves: Vec<([u8; 32], [u8; 32])>,
+    proof: &[u8],
+) -> Result<bool> {
+    let leaves = leaves
+        .into_iter()
+        .map(|(k, v)| (k.into(), v.into()))
+        .collect();
+    CompiledMerkleProof(proof.to_vec()).verify::<Blake2bHasher>(&root.into(), leaves)
+}
+
+/// Verify that `(block_hash, state_root)` was committed as the block-tree
+/// leaf at `number`, against the trusted root `block_root`.
+pub fn verify_block_proof(
+    block_root: [u8; 32],
+    number: u64,
+    block_hash: [u8; 32],
+    state_root: [u8; 32],
+    proof: &[u8],
+) -> Result<bool> {
+    let key = serialize_block_key(number);
+    let commitment = block_commitment(&block_hash, &state_root);
+    CompiledMerkleProof(proof.to_vec())
+        .verify::<Blake2bHasher>(&block_root.into(), vec![(key.into(), commitment.into())])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gw_common::smt::{default_store::DefaultStore, Store, H256, SMT};
+
+    #[test]
+    fn verify_account_proof_accepts_a_real_compiled_proof() {
+        let mut tree = SMT::new(H256::zero(), DefaultStore::<H256>::default());
+        let key: H256 = [1u8; 32].into();
+        let value: H256 = [2u8; 32].into();
+        tree.update(key, value).unwrap();
+        let proof = tree
+            .merkle_proof(vec![key])
+            .unwrap()
+            .compile(vec![(key, value)])
+            .unwrap()
+            .0;
+        let root: [u8; 32] = (*tree.root()).into();
+
+        assert!(verify_account_proof(root, vec![([1u8; 32], [2u8; 32])], &proof).unwrap());
+    }
+
+    #[test]
+    fn verify_block_proof_accepts_a_real_compiled_proof() {
+        let mut tree = SMT::new(H256::zero(), DefaultStore::<H256>::default());
+        let number = 5u64;
+        let block_hash = [3u8; 32];
+        let state_root = [4u8; 32];
+        let key: H256 = serialize_block_key(number).into();
+        let commitment: H256 = block_commitment(&block_hash, &state_root).into();
+        tree.update(key, commitment).unwrap();
+        let proof = tree
+            .merkle_proof(vec![key])
+            .unwrap()
+            .compile(vec![(key, commitment)])
+            .unwrap()
+            .0;
+        let root: [u8; 32] = (*tree.root()).into();
+
+        assert!(verify_block_proof(root, number, block_hash, state_root, &proof).unwrap());
+    }
+}
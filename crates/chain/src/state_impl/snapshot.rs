@@ -0,0 +1,279 @@
+use super::state_impl::StateImpl;
+use super::wrap_store::LeafScan;
+use anyhow::{anyhow, bail, Result};
+use gw_common::{
+    blake2b::new_blake2b,
+    smt::{Store, H256, SMT},
+    state::State,
+};
+use std::io::{Read, Write};
+
+/// Number of leaves grouped into a single snapshot chunk.
+const CHUNK_SIZE: usize = 4096;
+
+// Bumped whenever the chunk/manifest encoding changes, so old snapshots are rejected instead of misread.
+const SNAPSHOT_FORMAT_VERSION: u8 = 1;
+
+/// Header written once at the start of a snapshot, before any chunks.
+struct Manifest {
+    account_count: u32,
+    block_count: u64,
+    account_root: [u8; 32],
+    block_root: [u8; 32],
+    state_root_index_root: [u8; 32],
+}
+
+impl Manifest {
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&[SNAPSHOT_FORMAT_VERSION])?;
+        writer.write_all(&self.account_count.to_le_bytes())?;
+        writer.write_all(&self.block_count.to_le_bytes())?;
+        writer.write_all(&self.account_root)?;
+        writer.write_all(&self.block_root)?;
+        writer.write_all(&self.state_root_index_root)?;
+        Ok(())
+    }
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut version = [0u8; 1];
+        reader.read_exact(&mut version)?;
+        if version[0] != SNAPSHOT_FORMAT_VERSION {
+            bail!("unsupported snapshot format version: {}", version[0]);
+        }
+        let mut account_count = [0u8; 4];
+        reader.read_exact(&mut account_count)?;
+        let mut block_count = [0u8; 8];
+        reader.read_exact(&mut block_count)?;
+        let mut account_root = [0u8; 32];
+        reader.read_exact(&mut account_root)?;
+        let mut block_root = [0u8; 32];
+        reader.read_exact(&mut block_root)?;
+        let mut state_root_index_root = [0u8; 32];
+        reader.read_exact(&mut state_root_index_root)?;
+        Ok(Manifest {
+            account_count: u32::from_le_bytes(account_count),
+            block_count: u64::from_le_bytes(block_count),
+            account_root,
+            block_root,
+            state_root_index_root,
+        })
+    }
+}
+
+/// Which tree a chunk's leaves belong to, so import knows where to replay them.
+#[derive(Clone, Copy)]
+enum TreeTag {
+    Account,
+    Block,
+    StateRoot,
+}
+
+impl TreeTag {
+    fn to_byte(self) -> u8 {
+        match self {
+            TreeTag::Account => 0,
+            TreeTag::Block => 1,
+            TreeTag::StateRoot => 2,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<Self> {
+        match byte {
+            0 => Ok(TreeTag::Account),
+            1 => Ok(TreeTag::Block),
+            2 => Ok(TreeTag::StateRoot),
+            other => Err(anyhow!("unknown tree tag: {}", other)),
+        }
+    }
+}
+
+fn write_chunk<W: Write>(writer: &mut W, tag: TreeTag, leaves: &[(H256, H256)]) -> Result<()> {
+    let mut hasher = new_blake2b();
+    let mut body = Vec::with_capacity(leaves.len() * 64);
+    for (key, value) in leaves {
+        body.extend_from_slice(key.as_slice());
+        body.extend_from_slice(value.as_slice());
+    }
+    hasher.update(&body);
+    let mut checksum = [0u8; 32];
+    hasher.finalize(&mut checksum);
+
+    writer.write_all(&[SNAPSHOT_FORMAT_VERSION, tag.to_byte()])?;
+    writer.write_all(&checksum)?;
+    writer.write_all(&(leaves.len() as u32).to_le_bytes())?;
+    writer.write_all(&body)?;
+    Ok(())
+}
+
+fn read_chunk<R: Read>(reader: &mut R) -> Result<Option<(TreeTag, Vec<(H256, H256)>)>> {
+    let mut header = [0u8; 2];
+    if reader.read(&mut header[..1])? == 0 {
+        return Ok(None);
+    }
+    reader
+        .read_exact(&mut header[1..])
+        .map_err(|_| anyhow!("truncated snapshot chunk header"))?;
+    if header[0] != SNAPSHOT_FORMAT_VERSION {
+        bail!("unsupported snapshot format version: {}", header[0]);
+    }
+    let tag = TreeTag::from_byte(header[1])?;
+
+    let mut checksum = [0u8; 32];
+    reader.read_exact(&mut checksum)?;
+    let mut len_buf = [0u8; 4];
+    reader.read_exact(&mut len_buf)?;
+    let len = u32::from_le_bytes(len_buf) as usize;
+
+    let mut body = vec![0u8; len * 64];
+    reader.read_exact(&mut body)?;
+
+    let mut hasher = new_blake2b();
+    hasher.update(&body);
+    let mut actual_checksum = [0u8; 32];
+    hasher.finalize(&mut actual_checksum);
+    if actual_checksum != checksum {
+        bail!("snapshot chunk checksum mismatch");
+    }
+
+    let leaves = body
+        .chunks_exact(64)
+        .map(|chunk| {
+            let key: [u8; 32] = chunk[..32].try_into().expect("32 bytes");
+            let value: [u8; 32] = chunk[32..].try_into().expect("32 bytes");
+            (key.into(), value.into())
+        })
+        .collect();
+    Ok(Some((tag, leaves)))
+}
+
+impl<S: Store<H256>> StateImpl<S> {
+    // Chunk the account / block / state-root tree leaves into a snapshot a
+    // fresh node can bootstrap from instead of replaying every block.
+    pub fn export_snapshot<W: Write>(&self, writer: &mut W) -> Result<()>
+    where
+        S: LeafScan,
+    {
+        let manifest = Manifest {
+            account_count: self.account_count,
+            block_count: self.block_count,
+            account_root: self.calculate_root()?,
+            block_root: (*self.block_tree.root()).into(),
+            state_root_index_root: (*self.state_root_tree.root()).into(),
+        };
+        manifest.write(writer)?;
+
+        for (tag, leaves) in [
+            (TreeTag::Account, self.tree.store().iter_leaves()?),
+            (TreeTag::Block, self.block_tree.store().iter_leaves()?),
+            (
+                TreeTag::StateRoot,
+                self.state_root_tree.store().iter_leaves()?,
+            ),
+        ] {
+            for chunk in leaves.chunks(CHUNK_SIZE) {
+                write_chunk(writer, tag, chunk)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Rebuild a `StateImpl` from a snapshot produced by `export_snapshot`,
+    // rejecting it if any of the three recomputed roots disagree with the
+    // manifest instead of importing partially.
+    pub fn import_snapshot<R: Read>(reader: &mut R) -> Result<Self>
+    where
+        S: Default,
+    {
+        let manifest = Manifest::read(reader)?;
+        let mut state = Self::default();
+        state.account_count = manifest.account_count;
+        state.block_count = manifest.block_count;
+
+        while let Some((tag, leaves)) = read_chunk(reader)? {
+            match tag {
+                TreeTag::Account => {
+                    for (key, value) in leaves {
+                        state.tree.update(key, value)?;
+                    }
+                }
+                TreeTag::Block => {
+                    for (key, value) in leaves {
+                        state.block_tree.update(key, value)?;
+                    }
+                }
+                TreeTag::StateRoot => {
+                    for (key, value) in leaves {
+                        state.state_root_tree.update(key, value)?;
+                    }
+                }
+            }
+        }
+
+        let account_root = state.calculate_root()?;
+        if account_root != manifest.account_root {
+            bail!("imported account root does not match snapshot manifest");
+        }
+        let block_root: [u8; 32] = (*state.block_tree.root()).into();
+        if block_root != manifest.block_root {
+            bail!("imported block root does not match snapshot manifest");
+        }
+        let state_root_index_root: [u8; 32] = (*state.state_root_tree.root()).into();
+        if state_root_index_root != manifest.state_root_index_root {
+            bail!("imported state-root index does not match snapshot manifest");
+        }
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gw_common::smt::default_store::DefaultStore;
+
+    #[test]
+    fn export_snapshot_round_trips_through_import() {
+        let mut state: StateImpl<DefaultStore<H256>> = StateImpl::default();
+        state.update_raw([1u8; 32], [2u8; 32]).unwrap();
+        state.account_count = 1;
+        state.block_count = 1;
+        let key: H256 = gw_common::merkle_utils::serialize_block_key(1).into();
+        let account_root = state.calculate_root().unwrap();
+        state.block_tree.update(key, [9u8; 32].into()).unwrap();
+        state.state_root_tree.update(key, account_root.into()).unwrap();
+
+        let mut buf = Vec::new();
+        state.export_snapshot(&mut buf).unwrap();
+
+        let imported: StateImpl<DefaultStore<H256>> =
+            StateImpl::import_snapshot(&mut buf.as_slice()).unwrap();
+
+        assert_eq!(imported.calculate_root().unwrap(), state.calculate_root().unwrap());
+        assert_eq!(
+            (*imported.block_tree.root()).as_slice(),
+            (*state.block_tree.root()).as_slice()
+        );
+        assert_eq!(
+            imported.committed_state_root(1).unwrap(),
+            state.committed_state_root(1).unwrap()
+        );
+    }
+
+    #[test]
+    fn chunk_round_trips_and_detects_corruption() {
+        let leaves: Vec<(H256, H256)> = vec![
+            ([1u8; 32].into(), [2u8; 32].into()),
+            ([3u8; 32].into(), [4u8; 32].into()),
+        ];
+        let mut buf = Vec::new();
+        write_chunk(&mut buf, TreeTag::Account, &leaves).unwrap();
+
+        let (tag, decoded) = read_chunk(&mut buf.as_slice()).unwrap().unwrap();
+        assert!(matches!(tag, TreeTag::Account));
+        assert_eq!(decoded, leaves);
+
+        let mut corrupted = buf;
+        let body_start = 2 + 32 + 4;
+        corrupted[body_start] ^= 0xff;
+        assert!(read_chunk(&mut corrupted.as_slice()).is_err());
+    }
+}
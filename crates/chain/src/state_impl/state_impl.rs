@@ -1,16 +1,25 @@
 use super::overlay::OverlayState;
+use super::proof_verifier::block_commitment;
 use super::wrap_store::WrapStore;
 use anyhow::{anyhow, Result};
 use gw_common::{
     blake2b::new_blake2b,
     merkle_utils::serialize_block_key,
-    smt::{Store, H256, SMT},
+    smt::{CompiledMerkleProof, Store, H256, SMT},
     state::{Error, State},
 };
 use gw_types::{packed::L2Block, prelude::*};
 use parking_lot::Mutex;
 use std::sync::Arc;
 
+// Proves account key/value pairs had these values at a given block.
+pub struct HistoricalAccountProof {
+    pub block_proof: Vec<u8>,
+    pub block_hash: [u8; 32],
+    pub state_root: [u8; 32],
+    pub account_proof: Vec<u8>,
+}
+
 pub struct StateImpl<S> {
     tree: SMT<WrapStore<S>>,
     account_count: u32,
@@ -18,6 +27,8 @@ pub struct StateImpl<S> {
     // But the column must be difference, otherwise the keys may be collision with each other
     block_tree: SMT<WrapStore<S>>,
     block_count: u64,
+    // Local index of the account state root committed at each block number
+    state_root_tree: SMT<WrapStore<S>>,
 }
 
 impl<S: Store<H256>> StateImpl<S> {
@@ -26,12 +37,14 @@ impl<S: Store<H256>> StateImpl<S> {
         account_count: u32,
         block_tree: SMT<WrapStore<S>>,
         block_count: u64,
+        state_root_tree: SMT<WrapStore<S>>,
     ) -> Self {
         StateImpl {
             tree: account_tree,
             account_count,
             block_tree,
             block_count,
+            state_root_tree,
         }
     }
 
@@ -55,7 +68,11 @@ impl<S: Store<H256>> StateImpl<S> {
         };
         let block_number = raw.number().unpack();
         let key = serialize_block_key(block_number);
-        self.block_tree.update(key.into(), block_hash.into())?;
+        let state_root = self.calculate_root()?;
+        let commitment = block_commitment(&block_hash, &state_root);
+        self.block_tree.update(key.into(), commitment.into())?;
+        self.state_root_tree
+            .update(key.into(), state_root.into())?;
         Ok(())
     }
 
@@ -68,6 +85,81 @@ impl<S: Store<H256>> StateImpl<S> {
             .compile(vec![(key.into(), value.into())])?;
         Ok(proof.0)
     }
+
+    // `block_hash` is supplied by the caller since the block tree only retains the commitment.
+    pub fn historical_account_proof(
+        &self,
+        number: u64,
+        block_hash: [u8; 32],
+        leaves: Vec<([u8; 32], [u8; 32])>,
+    ) -> Result<HistoricalAccountProof> {
+        let key = serialize_block_key(number);
+        let state_root: [u8; 32] = self.state_root_tree.get(&key.into())?.into();
+
+        let block_proof = self.block_merkle_proof(number)?;
+
+        let historical_tree = SMT::new(state_root.into(), self.tree.store().clone());
+        let keys = leaves.iter().map(|(k, _)| (*k).into()).collect();
+        let account_proof = historical_tree
+            .merkle_proof(keys)?
+            .compile(
+                leaves
+                    .into_iter()
+                    .map(|(k, v)| (k.into(), v.into()))
+                    .collect(),
+            )?
+            .0;
+
+        Ok(HistoricalAccountProof {
+            block_proof,
+            block_hash,
+            state_root,
+            account_proof,
+        })
+    }
+
+    // The committed account root for `number`, used by `state_at_block`.
+    pub fn committed_state_root(&self, number: u64) -> Result<[u8; 32], Error> {
+        let key = serialize_block_key(number);
+        Ok(self.state_root_tree.get(&key.into())?.into())
+    }
+
+    // Read-only overlay anchored at a historical root, same as `new_overlay` but for a past block.
+    pub fn state_at_block(&self, number: u64) -> Result<OverlayState<WrapStore<S>>> {
+        let root = self.committed_state_root(number)?;
+        let account_count = self
+            .get_account_count()
+            .map_err(|err| anyhow!("get account count error: {:?}", err))?;
+        let store = self.tree.store().clone();
+        Ok(OverlayState::new(root.into(), store, account_count))
+    }
+}
+
+// Verifies a `HistoricalAccountProof` against a trusted block-tree root, without any store.
+pub fn verify_historical_account_proof(
+    block_root: [u8; 32],
+    number: u64,
+    leaves: Vec<([u8; 32], [u8; 32])>,
+    proof: HistoricalAccountProof,
+) -> Result<bool> {
+    let key: H256 = serialize_block_key(number).into();
+    let commitment = block_commitment(&proof.block_hash, &proof.state_root);
+
+    let block_ok = CompiledMerkleProof(proof.block_proof).verify::<gw_common::blake2b::Blake2bHasher>(
+        &block_root.into(),
+        vec![(key, commitment.into())],
+    )?;
+
+    let account_ok = CompiledMerkleProof(proof.account_proof)
+        .verify::<gw_common::blake2b::Blake2bHasher>(
+            &proof.state_root.into(),
+            leaves
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )?;
+
+    Ok(block_ok && account_ok)
 }
 
 impl<S: Store<H256> + Default> Default for StateImpl<S> {
@@ -80,11 +172,16 @@ impl<S: Store<H256> + Default> Default for StateImpl<S> {
             H256::zero(),
             WrapStore::new(Arc::new(Mutex::new(S::default()))),
         );
+        let state_root_tree = SMT::new(
+            H256::zero(),
+            WrapStore::new(Arc::new(Mutex::new(S::default()))),
+        );
         StateImpl {
             tree,
             account_count: 0,
             block_tree,
             block_count: 0,
+            state_root_tree,
         }
     }
 }
@@ -123,4 +220,53 @@ impl<S: Store<H256>> State for StateImpl<S> {
             .0;
         Ok(proof)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gw_common::smt::default_store::DefaultStore;
+
+    #[test]
+    fn historical_account_proof_round_trips_through_verify() {
+        let mut state: StateImpl<DefaultStore<H256>> = StateImpl::default();
+        state.update_raw([1u8; 32], [2u8; 32]).unwrap();
+
+        let block_hash = [7u8; 32];
+        let number = 1u64;
+        let key = serialize_block_key(number);
+        let state_root = state.calculate_root().unwrap();
+        let commitment = block_commitment(&block_hash, &state_root);
+        state.block_tree.update(key.into(), commitment.into()).unwrap();
+        state
+            .state_root_tree
+            .update(key.into(), state_root.into())
+            .unwrap();
+
+        let leaves = vec![([1u8; 32], [2u8; 32])];
+        let proof = state
+            .historical_account_proof(number, block_hash, leaves.clone())
+            .unwrap();
+        let block_root: [u8; 32] = (*state.block_tree.root()).into();
+
+        assert!(verify_historical_account_proof(block_root, number, leaves, proof).unwrap());
+    }
+
+    #[test]
+    fn state_at_block_reads_a_past_root_without_touching_current_state() {
+        let mut state: StateImpl<DefaultStore<H256>> = StateImpl::default();
+        state.update_raw([1u8; 32], [2u8; 32]).unwrap();
+        let key = serialize_block_key(1);
+        let past_root = state.calculate_root().unwrap();
+        state.state_root_tree.update(key.into(), past_root.into()).unwrap();
+
+        state.update_raw([1u8; 32], [3u8; 32]).unwrap();
+        let current_root = state.calculate_root().unwrap();
+        assert_ne!(current_root, past_root);
+
+        assert_eq!(state.committed_state_root(1).unwrap(), past_root);
+        let overlay = state.state_at_block(1).unwrap();
+        assert_eq!(overlay.calculate_root().unwrap(), past_root);
+        assert_eq!(overlay.get_raw(&[1u8; 32]).unwrap(), [2u8; 32]);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,70 @@
+use anyhow::Result;
+use gw_common::smt::{default_store::DefaultStore, BranchKey, BranchNode, Store, H256};
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// Shares one logical backing store between the mutable tree that owns it
+/// and any read-only clones (`new_overlay`, `state_at_block`).
+pub struct WrapStore<S> {
+    inner: Arc<Mutex<S>>,
+}
+
+impl<S> WrapStore<S> {
+    pub fn new(inner: Arc<Mutex<S>>) -> Self {
+        WrapStore { inner }
+    }
+}
+
+impl<S> Clone for WrapStore<S> {
+    fn clone(&self) -> Self {
+        WrapStore {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<S: Store<H256>> Store<H256> for WrapStore<S> {
+    fn get_branch(&self, branch_key: &BranchKey) -> Result<Option<BranchNode>, gw_common::error::Error> {
+        self.inner.lock().get_branch(branch_key)
+    }
+    fn get_leaf(&self, leaf_key: &H256) -> Result<Option<H256>, gw_common::error::Error> {
+        self.inner.lock().get_leaf(leaf_key)
+    }
+    fn insert_branch(
+        &mut self,
+        branch_key: BranchKey,
+        branch: BranchNode,
+    ) -> Result<(), gw_common::error::Error> {
+        self.inner.lock().insert_branch(branch_key, branch)
+    }
+    fn insert_leaf(&mut self, leaf_key: H256, leaf: H256) -> Result<(), gw_common::error::Error> {
+        self.inner.lock().insert_leaf(leaf_key, leaf)
+    }
+    fn remove_branch(&mut self, branch_key: &BranchKey) -> Result<(), gw_common::error::Error> {
+        self.inner.lock().remove_branch(branch_key)
+    }
+    fn remove_leaf(&mut self, leaf_key: &H256) -> Result<(), gw_common::error::Error> {
+        self.inner.lock().remove_leaf(leaf_key)
+    }
+}
+
+/// A `Store` that can additionally be scanned in full. Backing stores used
+/// for snapshot export must implement this; import does not need it. Only
+/// the in-memory `DefaultStore` implements it here.
+pub trait LeafScan {
+    fn scan_leaves(&self) -> Vec<(H256, H256)>;
+}
+
+impl LeafScan for DefaultStore<H256> {
+    fn scan_leaves(&self) -> Vec<(H256, H256)> {
+        self.leaves_map().iter().map(|(k, v)| (*k, *v)).collect()
+    }
+}
+
+impl<S: LeafScan> WrapStore<S> {
+    /// Enumerate every `(key, value)` leaf currently committed to the tree
+    /// this store backs. Used by snapshot export.
+    pub fn iter_leaves(&self) -> Result<Vec<(H256, H256)>> {
+        Ok(self.inner.lock().scan_leaves())
+    }
+}